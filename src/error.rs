@@ -0,0 +1,50 @@
+//! Error types used throughout the KMS
+
+use std::fmt::{self, Display};
+use std::io;
+
+/// Errors originating anywhere in the KMS
+#[derive(Debug)]
+pub enum Error {
+    /// Input/output error talking to a validator or the filesystem
+    Io(io::Error),
+
+    /// A message could not be parsed or decoded
+    Parse(String),
+
+    /// A key is malformed, of the wrong algorithm, or has no signing key
+    /// registered for it
+    InvalidKey(String),
+
+    /// A signature did not verify against the key and message it claims to sign
+    InvalidSignature(String),
+
+    /// Signing was refused by a safety subsystem (e.g. slashing protection)
+    SigningProhibited(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Parse(msg) => write!(f, "parse error: {}", msg),
+            Error::InvalidKey(msg) => write!(f, "invalid key: {}", msg),
+            Error::InvalidSignature(msg) => write!(f, "invalid signature: {}", msg),
+            Error::SigningProhibited(msg) => write!(f, "refusing to sign: {}", msg),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<signatory::error::Error> for Error {
+    fn from(err: signatory::error::Error) -> Self {
+        Error::InvalidKey(err.to_string())
+    }
+}
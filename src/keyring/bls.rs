@@ -0,0 +1,158 @@
+//! BLS12-381 signing keys (min-pubkey-size variant), used by BLS-based
+//! consensus participants such as Eth2 sync-committee members
+
+use std::collections::HashMap;
+
+use blst::min_pk::{AggregateSignature, PublicKey as BlstPublicKey, SecretKey, Signature as BlstSignature};
+use blst::BLST_ERROR;
+
+use error::Error;
+
+/// Domain separation tag for the min-pubkey-size BLS12-381 ciphersuite
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Size of a min-pubkey-size BLS12-381 public key
+pub const PUBLIC_KEY_SIZE: usize = 48;
+
+/// Size of a min-pubkey-size BLS12-381 signature
+pub const SIGNATURE_SIZE: usize = 96;
+
+/// A BLS12-381 public key (min-pubkey-size / G1 variant)
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PublicKey(pub [u8; PUBLIC_KEY_SIZE]);
+
+/// A BLS12-381 signature (min-pubkey-size / G2 variant)
+#[derive(Clone, Debug)]
+pub struct Signature(pub [u8; SIGNATURE_SIZE]);
+
+/// A keyring of BLS12-381 signing keys, indexed by public key
+#[derive(Default)]
+pub struct Keyring {
+    keys: HashMap<PublicKey, SecretKey>,
+}
+
+impl Keyring {
+    /// Create a new keyring from the given secret keys
+    pub fn new(secret_keys: Vec<SecretKey>) -> Result<Self, Error> {
+        let mut keys = HashMap::new();
+
+        for sk in secret_keys {
+            keys.insert(PublicKey(sk.sk_to_pk().to_bytes()), sk);
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Sign a message with the key corresponding to the given public key
+    pub fn sign(&self, public_key: &PublicKey, msg: &[u8]) -> Result<Signature, Error> {
+        let sk = self.keys.get(public_key).ok_or_else(|| {
+            Error::InvalidKey("no BLS12-381 signing key registered for the given public key".into())
+        })?;
+
+        Ok(Signature(sk.sign(msg, DST, &[]).to_bytes()))
+    }
+
+    /// Verify a signature was produced by the given public key over `msg`
+    pub fn verify(public_key: &PublicKey, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        let pk = BlstPublicKey::from_bytes(&public_key.0)
+            .map_err(|_| Error::InvalidKey("malformed BLS12-381 public key".into()))?;
+        let sig = BlstSignature::from_bytes(&signature.0)
+            .map_err(|_| Error::InvalidSignature("malformed BLS12-381 signature".into()))?;
+
+        match sig.verify(true, msg, DST, &[], &pk, true) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            _ => Err(Error::InvalidSignature("BLS12-381 signature did not verify".into())),
+        }
+    }
+
+    /// Verify an aggregate signature over one message per public key, e.g. a
+    /// sync-committee aggregate signature over the same beacon block root
+    /// signed by many validators
+    pub fn aggregate_verify(
+        public_keys: &[PublicKey],
+        msgs: &[&[u8]],
+        signature: &Signature,
+    ) -> Result<(), Error> {
+        if public_keys.len() != msgs.len() {
+            return Err(Error::InvalidSignature(
+                "aggregate verify requires exactly one message per public key".into(),
+            ));
+        }
+
+        let pks = public_keys
+            .iter()
+            .map(|pk| {
+                BlstPublicKey::from_bytes(&pk.0)
+                    .map_err(|_| Error::InvalidKey("malformed BLS12-381 public key".into()))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let pk_refs: Vec<&BlstPublicKey> = pks.iter().collect();
+
+        let sig = BlstSignature::from_bytes(&signature.0)
+            .map_err(|_| Error::InvalidSignature("malformed BLS12-381 signature".into()))?;
+
+        match sig.aggregate_verify(true, msgs, DST, &pk_refs, true) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            _ => Err(Error::InvalidSignature(
+                "BLS12-381 aggregate signature did not verify".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_secret_key(seed: u8) -> SecretKey {
+        SecretKey::key_gen(&[seed; 32], &[]).unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        let sk = test_secret_key(1);
+        let pk = PublicKey(sk.sk_to_pk().to_bytes());
+        let keyring = Keyring::new(vec![sk]).unwrap();
+
+        let sig = keyring.sign(&pk, b"msg").unwrap();
+        Keyring::verify(&pk, b"msg", &sig).unwrap();
+    }
+
+    #[test]
+    fn test_verify_rejects_wrong_public_key() {
+        let sk = test_secret_key(2);
+        let pk = PublicKey(sk.sk_to_pk().to_bytes());
+        let other_pk = PublicKey(test_secret_key(3).sk_to_pk().to_bytes());
+        let keyring = Keyring::new(vec![sk]).unwrap();
+
+        let sig = keyring.sign(&pk, b"msg").unwrap();
+        assert!(Keyring::verify(&other_pk, b"msg", &sig).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_verify_roundtrip() {
+        let sk_a = test_secret_key(4);
+        let sk_b = test_secret_key(5);
+        let pk_a = PublicKey(sk_a.sk_to_pk().to_bytes());
+        let pk_b = PublicKey(sk_b.sk_to_pk().to_bytes());
+
+        let keyring = Keyring::new(vec![sk_a, sk_b]).unwrap();
+        let sig_a = keyring.sign(&pk_a, b"msg-a").unwrap();
+        let sig_b = keyring.sign(&pk_b, b"msg-b").unwrap();
+
+        let mut agg = AggregateSignature::from_signature(&BlstSignature::from_bytes(&sig_a.0).unwrap());
+        agg.add_signature(&BlstSignature::from_bytes(&sig_b.0).unwrap(), true)
+            .unwrap();
+        let aggregate = Signature(agg.to_signature().to_bytes());
+
+        Keyring::aggregate_verify(&[pk_a, pk_b], &[b"msg-a", b"msg-b"], &aggregate).unwrap();
+    }
+
+    #[test]
+    fn test_aggregate_verify_rejects_mismatched_lengths() {
+        let pk = PublicKey(test_secret_key(6).sk_to_pk().to_bytes());
+        let signature = Signature([0u8; SIGNATURE_SIZE]);
+
+        assert!(Keyring::aggregate_verify(&[pk], &[], &signature).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+//! Ed25519 signing keys
+
+use std::collections::HashMap;
+
+use signatory::ed25519::{Signature, Signer, Verifier};
+pub use signatory::ed25519::{PublicKey, SIGNATURE_SIZE};
+use signatory::public_key::PublicKeyed;
+use signatory_dalek::{Ed25519Signer, Ed25519Verifier};
+
+use error::Error;
+
+/// A keyring of Ed25519 signing keys, indexed by public key
+#[derive(Default)]
+pub struct Keyring {
+    keys: HashMap<PublicKey, Ed25519Signer>,
+}
+
+impl Keyring {
+    /// Create a new keyring from the given signing keys
+    pub fn new(signers: Vec<Ed25519Signer>) -> Result<Self, Error> {
+        let mut keys = HashMap::new();
+
+        for signer in signers {
+            let public_key = signer
+                .public_key()
+                .map_err(|e| Error::InvalidKey(e.to_string()))?;
+
+            keys.insert(public_key, signer);
+        }
+
+        Ok(Self { keys })
+    }
+
+    /// Sign a message with the key corresponding to the given public key
+    pub fn sign(&self, public_key: &PublicKey, msg: &[u8]) -> Result<Signature, Error> {
+        let signer = self.keys.get(public_key).ok_or_else(|| {
+            Error::InvalidKey("no Ed25519 signing key registered for the given public key".into())
+        })?;
+
+        signer
+            .sign(msg)
+            .map_err(|e| Error::InvalidSignature(e.to_string()))
+    }
+
+    /// Verify a signature was produced by the given public key over `msg`
+    pub fn verify(public_key: &PublicKey, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        Ed25519Verifier::from(public_key)
+            .verify(msg, signature)
+            .map_err(|e| Error::InvalidSignature(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_keyring_is_empty_when_given_no_signers() {
+        let keyring = Keyring::new(vec![]).unwrap();
+        assert!(keyring.keys.is_empty());
+    }
+}
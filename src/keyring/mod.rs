@@ -0,0 +1,173 @@
+//! A keyring of signing keys spanning the algorithms this KMS supports:
+//! Ed25519 (Tendermint validator consensus messages), secp256k1
+//! (Cosmos-ecosystem accounts), and BLS12-381 (Eth2-style sync-committee
+//! aggregate signatures).
+
+pub mod bls;
+pub mod ed25519;
+pub mod secp256k1;
+
+use error::Error;
+
+/// Which signing algorithm a key or signature belongs to
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum KeyType {
+    /// Ed25519, used for Tendermint validator votes and proposals
+    Ed25519,
+
+    /// secp256k1 ECDSA, used for Cosmos-ecosystem accounts
+    Secp256k1,
+
+    /// BLS12-381 (min-pubkey-size), used for BLS-based consensus participants
+    Bls12381,
+}
+
+/// A public key for any of the algorithms this KMS can sign with
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum PublicKey {
+    /// Ed25519 public key
+    Ed25519(ed25519::PublicKey),
+
+    /// secp256k1 public key
+    Secp256k1(secp256k1::PublicKey),
+
+    /// BLS12-381 public key
+    Bls12381(bls::PublicKey),
+}
+
+impl PublicKey {
+    /// Parse a public key of the given type from its raw encoding
+    pub fn from_bytes(key_type: KeyType, bytes: &[u8]) -> Result<Self, Error> {
+        match key_type {
+            KeyType::Ed25519 => Ok(PublicKey::Ed25519(ed25519::PublicKey::from_bytes(bytes)?)),
+            KeyType::Secp256k1 => Ok(PublicKey::Secp256k1(secp256k1::PublicKey::from_bytes(
+                bytes,
+            )?)),
+            KeyType::Bls12381 => {
+                if bytes.len() != bls::PUBLIC_KEY_SIZE {
+                    return Err(Error::InvalidKey(
+                        "wrong length for a BLS12-381 public key".into(),
+                    ));
+                }
+                let mut buf = [0u8; bls::PUBLIC_KEY_SIZE];
+                buf.copy_from_slice(bytes);
+                Ok(PublicKey::Bls12381(bls::PublicKey(buf)))
+            }
+        }
+    }
+
+    /// Which algorithm this public key belongs to
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            PublicKey::Ed25519(_) => KeyType::Ed25519,
+            PublicKey::Secp256k1(_) => KeyType::Secp256k1,
+            PublicKey::Bls12381(_) => KeyType::Bls12381,
+        }
+    }
+}
+
+/// A signature produced by any of the algorithms this KMS can sign with
+#[derive(Clone, Debug)]
+pub enum Signature {
+    /// Ed25519 signature
+    Ed25519(ed25519::Signature),
+
+    /// secp256k1 ECDSA signature
+    Secp256k1(secp256k1::Signature),
+
+    /// BLS12-381 signature
+    Bls12381(bls::Signature),
+}
+
+impl Signature {
+    /// Raw bytes of this signature, correctly sized for its algorithm
+    /// (64 bytes for Ed25519/secp256k1, 96 bytes for BLS12-381)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Signature::Ed25519(sig) => sig.as_bytes().to_vec(),
+            Signature::Secp256k1(sig) => sig.as_bytes().to_vec(),
+            Signature::Bls12381(sig) => sig.0.to_vec(),
+        }
+    }
+}
+
+/// A keyring of signing keys spanning Ed25519, secp256k1, and BLS12-381
+#[derive(Default)]
+pub struct Keyring {
+    ed25519: ed25519::Keyring,
+    secp256k1: secp256k1::Keyring,
+    bls12381: bls::Keyring,
+}
+
+impl Keyring {
+    /// Create a new keyring from per-algorithm keyrings
+    pub fn new(ed25519: ed25519::Keyring, secp256k1: secp256k1::Keyring, bls12381: bls::Keyring) -> Self {
+        Self {
+            ed25519,
+            secp256k1,
+            bls12381,
+        }
+    }
+
+    /// Sign a message with the key corresponding to the given public key,
+    /// dispatching to the backend matching its algorithm
+    pub fn sign(&self, public_key: &PublicKey, msg: &[u8]) -> Result<Signature, Error> {
+        match public_key {
+            PublicKey::Ed25519(pk) => Ok(Signature::Ed25519(self.ed25519.sign(pk, msg)?)),
+            PublicKey::Secp256k1(pk) => Ok(Signature::Secp256k1(self.secp256k1.sign(pk, msg)?)),
+            PublicKey::Bls12381(pk) => Ok(Signature::Bls12381(self.bls12381.sign(pk, msg)?)),
+        }
+    }
+
+    /// Verify a signature was produced by the given public key over `msg`
+    pub fn verify(public_key: &PublicKey, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        match (public_key, signature) {
+            (PublicKey::Ed25519(pk), Signature::Ed25519(sig)) => {
+                ed25519::Keyring::verify(pk, msg, sig)
+            }
+            (PublicKey::Secp256k1(pk), Signature::Secp256k1(sig)) => {
+                secp256k1::Keyring::verify(pk, msg, sig)
+            }
+            (PublicKey::Bls12381(pk), Signature::Bls12381(sig)) => {
+                bls::Keyring::verify(pk, msg, sig)
+            }
+            _ => Err(Error::InvalidKey(
+                "public key and signature are of different algorithms".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_bytes_rejects_wrong_length_bls_key() {
+        match PublicKey::from_bytes(KeyType::Bls12381, &[0u8; 10]) {
+            Err(Error::InvalidKey(_)) => {}
+            other => panic!("expected InvalidKey, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_empty_ed25519_key() {
+        assert!(PublicKey::from_bytes(KeyType::Ed25519, &[]).is_err());
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_empty_secp256k1_key() {
+        assert!(PublicKey::from_bytes(KeyType::Secp256k1, &[]).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_mismatched_algorithms() {
+        let pk = PublicKey::Bls12381(bls::PublicKey([0u8; bls::PUBLIC_KEY_SIZE]));
+        let sig = Signature::Ed25519(ed25519::Signature([0u8; ed25519::SIGNATURE_SIZE]));
+
+        match Keyring::verify(&pk, b"msg", &sig) {
+            Err(Error::InvalidKey(_)) => {}
+            other => panic!("expected InvalidKey, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! RPC messages exchanged between the KMS and a validator node
+
+use std::io::{Cursor, Read};
+
+use amino::{compute_disfix, consume_length};
+use error::Error;
+use keyring::KeyType;
+
+/// Amino prefix used by vote sign requests, see `types::vote::Vote`
+const VOTE_PREFIX: &str = "tendermint/socketpv/SignVoteMsg";
+
+/// Amino prefix used by proposal sign requests, see `types::proposal::Proposal`
+const PROPOSAL_PREFIX: &str = "tendermint/socketpv/SignProposalMsg";
+
+/// A request read from the validator's socket connection
+pub enum Request {
+    /// Sign a `Vote` (always Ed25519)
+    Sign(SignRequest),
+
+    /// Sign a `Proposal` (always Ed25519)
+    SignProposal(SignRequest),
+
+    /// Sign an opaque message with a particular key and algorithm, for
+    /// non-Tendermint-consensus signers (e.g. secp256k1 or BLS12-381)
+    SignRaw(SignRequest),
+
+    /// Terminate the session (debug builds only, used by integration tests)
+    #[cfg(debug_assertions)]
+    PoisonPill,
+}
+
+impl Request {
+    /// Read the next length-prefixed request from the given reader.
+    ///
+    /// Each frame is `[public_key_len: u8][public_key][message]`, where
+    /// `message` is the Amino-encoded `Vote`/`Proposal`, or (for a raw sign
+    /// request) a leading key-type tag byte followed by the payload. The
+    /// public key segment tells the KMS which registered key to dispatch
+    /// the request to.
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut len_bytes = [0u8; 4];
+        reader.read_exact(&mut len_bytes)?;
+        let len = u32::from_be_bytes(len_bytes) as usize;
+
+        let mut frame = vec![0u8; len];
+        reader.read_exact(&mut frame)?;
+
+        let (public_key, msg) = split_public_key(&frame)?;
+
+        match classify(msg)? {
+            Kind::Vote => Ok(Request::Sign(SignRequest {
+                key_type: KeyType::Ed25519,
+                public_key,
+                msg: msg.to_vec(),
+            })),
+            Kind::Proposal => Ok(Request::SignProposal(SignRequest {
+                key_type: KeyType::Ed25519,
+                public_key,
+                msg: msg.to_vec(),
+            })),
+            Kind::Raw(key_type) => Ok(Request::SignRaw(SignRequest {
+                key_type,
+                public_key,
+                // strip the leading key-type tag byte consumed by `classify`
+                msg: msg[1..].to_vec(),
+            })),
+        }
+    }
+}
+
+/// Split a frame into its length-prefixed public key segment and the
+/// remaining message bytes
+fn split_public_key(frame: &[u8]) -> Result<(Vec<u8>, &[u8]), Error> {
+    let pk_len = *frame
+        .first()
+        .ok_or_else(|| Error::Parse("empty sign request frame".into()))? as usize;
+
+    if frame.len() < 1 + pk_len {
+        return Err(Error::Parse(
+            "sign request frame shorter than its public key field".into(),
+        ));
+    }
+
+    Ok((frame[1..1 + pk_len].to_vec(), &frame[1 + pk_len..]))
+}
+
+/// Which kind of sign request an incoming message carries
+enum Kind {
+    Vote,
+    Proposal,
+    /// Not a recognized Amino `Vote`/`Proposal`: a raw message tagged with
+    /// its algorithm in its leading byte
+    Raw(KeyType),
+}
+
+/// Peek past the amino length prefix and match the message's disfix against
+/// the known `Vote`/`Proposal` prefixes. Anything else is treated as a raw
+/// sign request, tagged by a leading key-type byte (0 = Ed25519, 1 =
+/// secp256k1, 2 = BLS12-381).
+fn classify(msg: &[u8]) -> Result<Kind, Error> {
+    let mut buf = Cursor::new(msg);
+    if consume_length(&mut buf).is_ok() {
+        let body = &msg[buf.position() as usize..];
+
+        let (_, vote_prefix) = compute_disfix(VOTE_PREFIX);
+        let (_, proposal_prefix) = compute_disfix(PROPOSAL_PREFIX);
+
+        if body.starts_with(&vote_prefix[..3]) {
+            return Ok(Kind::Vote);
+        }
+        if body.starts_with(&proposal_prefix[..3]) {
+            return Ok(Kind::Proposal);
+        }
+    }
+
+    match msg.first() {
+        Some(0) => Ok(Kind::Raw(KeyType::Ed25519)),
+        Some(1) => Ok(Kind::Raw(KeyType::Secp256k1)),
+        Some(2) => Ok(Kind::Raw(KeyType::Bls12381)),
+        _ => Err(Error::Parse(
+            "sign request does not match a known Vote, Proposal, or raw key-type tag".into(),
+        )),
+    }
+}
+
+/// A response written back to the validator's socket connection
+pub enum Response {
+    /// A completed vote signature
+    Sign(SignResponse),
+
+    /// A completed proposal signature
+    SignProposal(SignResponse),
+
+    /// A completed raw signature
+    SignRaw(SignResponse),
+}
+
+impl Response {
+    /// Serialize this response as a length-prefixed frame
+    pub fn to_vec(&self) -> Vec<u8> {
+        let sig = match self {
+            Response::Sign(resp) | Response::SignProposal(resp) | Response::SignRaw(resp) => {
+                &resp.sig
+            }
+        };
+
+        let mut buf = (sig.len() as u32).to_be_bytes().to_vec();
+        buf.extend_from_slice(sig);
+        buf
+    }
+}
+
+/// Request to sign a consensus message with a particular key
+pub struct SignRequest {
+    /// Algorithm of the requested signing key
+    pub key_type: KeyType,
+
+    /// Public key identifying which signing key to use
+    pub public_key: Vec<u8>,
+
+    /// Message to sign: an Amino-encoded `Vote`/`Proposal`, or (for
+    /// `Request::SignRaw`) the raw payload to sign directly
+    pub msg: Vec<u8>,
+}
+
+/// Response containing the completed signature, correctly sized for its
+/// algorithm (64 bytes for Ed25519/secp256k1, 96 bytes for BLS12-381)
+pub struct SignResponse {
+    /// The resulting signature
+    pub sig: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn frame_to_wire(frame: &[u8]) -> Vec<u8> {
+        let mut wire = (frame.len() as u32).to_be_bytes().to_vec();
+        wire.extend_from_slice(frame);
+        wire
+    }
+
+    #[test]
+    fn test_read_splits_public_key_from_raw_request() {
+        let pubkey = vec![0xaau8; 33];
+        let mut frame = vec![pubkey.len() as u8];
+        frame.extend_from_slice(&pubkey);
+        frame.push(1); // secp256k1 raw tag
+        frame.extend_from_slice(b"payload");
+
+        let mut reader = Cursor::new(frame_to_wire(&frame));
+        match Request::read(&mut reader).unwrap() {
+            Request::SignRaw(req) => {
+                assert_eq!(req.public_key, pubkey);
+                assert_eq!(req.key_type, KeyType::Secp256k1);
+                assert_eq!(req.msg, b"payload".to_vec());
+            }
+            _ => panic!("expected a raw sign request"),
+        }
+    }
+
+    #[test]
+    fn test_read_rejects_frame_shorter_than_declared_public_key_length() {
+        let frame = vec![5u8, 1, 2, 3];
+        let mut reader = Cursor::new(frame_to_wire(&frame));
+        assert!(Request::read(&mut reader).is_err());
+    }
+}
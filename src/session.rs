@@ -2,33 +2,75 @@
 
 use std::io::Write;
 use std::net::TcpStream;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use ed25519::{Keyring, PublicKey};
+use sha2::{Digest, Sha256};
+
 use error::Error;
+use keyring::{ed25519, Keyring, PublicKey, Signature};
 use rpc::{Request, Response, SignRequest, SignResponse};
+use secret_connection::SecretConnection;
+use slashing_protection::SlashingProtection;
+use types::{BlockID, Proposal, TendermintSign, ValidatorAddress, Vote};
 
-/// A (soon-to-be-encrypted) session with a validator node
+/// An encrypted session with a validator node, authenticated via
+/// Tendermint's Secret Connection handshake
 pub struct Session {
-    /// TCP connection to a validator node
-    socket: TcpStream,
+    /// Chain ID of the validator this session belongs to
+    chain_id: String,
+
+    /// Encrypted connection to a validator node
+    socket: SecretConnection,
 
-    /// Keyring of signature keys
+    /// Keyring of signature keys, spanning Ed25519, secp256k1, and BLS12-381
     keyring: Arc<Keyring>,
+
+    /// High-water-mark store, consulted before every signature
+    slashing_protection: SlashingProtection,
 }
 
 impl Session {
-    /// Create a new session with the validator at the given address/port
-    pub fn new(addr: &str, port: u16, keyring: Arc<Keyring>) -> Result<Self, Error> {
+    /// Create a new session with the validator at the given address/port,
+    /// performing the Secret Connection handshake and authenticating with
+    /// `identity_pubkey` (an Ed25519 key held by `keyring`)
+    pub fn new(
+        addr: &str,
+        port: u16,
+        chain_id: &str,
+        keyring: Arc<Keyring>,
+        identity_pubkey: &ed25519::PublicKey,
+        slashing_protection_path: impl Into<PathBuf>,
+    ) -> Result<Self, Error> {
         debug!("Connecting to {}:{}...", addr, port);
-        let socket = TcpStream::connect(format!("{}:{}", addr, port))?;
-        Ok(Self { socket, keyring })
+        let tcp_socket = TcpStream::connect(format!("{}:{}", addr, port))?;
+        let socket = SecretConnection::new(tcp_socket, &keyring, identity_pubkey)?;
+        let slashing_protection = SlashingProtection::load(slashing_protection_path)?;
+
+        debug!(
+            "Secret Connection established with validator {:?}",
+            socket.remote_pubkey()
+        );
+
+        Ok(Self {
+            chain_id: chain_id.to_owned(),
+            socket,
+            keyring,
+            slashing_protection,
+        })
+    }
+
+    /// Long-term Ed25519 public key the remote validator authenticated with
+    pub fn remote_pubkey(&self) -> &ed25519::PublicKey {
+        self.socket.remote_pubkey()
     }
 
     /// Handle an incoming request from the validator
     pub fn handle_request(&mut self) -> Result<bool, Error> {
         let response = match Request::read(&mut self.socket)? {
             Request::Sign(ref req) => self.sign(req)?,
+            Request::SignProposal(ref req) => self.sign_proposal(req)?,
+            Request::SignRaw(ref req) => self.sign_raw(req)?,
             #[cfg(debug_assertions)]
             Request::PoisonPill => return Ok(false),
         };
@@ -37,13 +79,108 @@ impl Session {
         Ok(true)
     }
 
-    /// Perform a digital signature operation
+    /// Perform a digital signature operation over a `Vote`
     fn sign(&mut self, request: &SignRequest) -> Result<Response, Error> {
-        let pk = PublicKey::from_bytes(&request.public_key)?;
-        let signature = self.keyring.sign(&pk, &request.msg)?;
+        let pk = ed25519::PublicKey::from_bytes(&request.public_key)?;
+        let vote = Vote::deserialize(&request.msg).map_err(|e| Error::Parse(e.to_string()))?;
+
+        // `validator_address` is decoded straight off the wire and is not
+        // part of `sign_bytes` (CanonicalVote omits it), so it must never be
+        // trusted to key the high-water mark: a forged address would let a
+        // second, conflicting vote slip in under a fresh `ConsensusKey`. Key
+        // on the signing key's own derived identity instead, same as
+        // `sign_proposal`, so a vote can only ever collide with its own past
+        // votes and proposals.
+        let validator_address = address_from_pubkey(&pk);
+
+        self.slashing_protection.check_and_update(
+            &self.chain_id,
+            &validator_address,
+            vote.height(),
+            vote.round(),
+            vote.step(),
+            Some(vote.block_id()),
+        )?;
+
+        let sign_bytes = vote.sign_bytes(&self.chain_id);
+        let signature = self.ed25519_sign(&pk, &sign_bytes)?;
 
         Ok(Response::Sign(SignResponse {
             sig: signature.as_bytes().to_vec(),
         }))
     }
+
+    /// Perform a digital signature operation over a `Proposal`
+    fn sign_proposal(&mut self, request: &SignRequest) -> Result<Response, Error> {
+        let pk = ed25519::PublicKey::from_bytes(&request.public_key)?;
+        let proposal =
+            Proposal::deserialize(&request.msg).map_err(|e| Error::Parse(e.to_string()))?;
+
+        // Proposals carry no validator address of their own; derive the
+        // same 20-byte identity Tendermint uses for votes so proposals and
+        // votes share one high-water-mark namespace per validator.
+        let validator_address = address_from_pubkey(&pk);
+
+        let proposed_block_id = BlockID {
+            hash: vec![],
+            parts_header: proposal.block_parts_header().clone(),
+        };
+
+        self.slashing_protection.check_and_update(
+            &self.chain_id,
+            &validator_address,
+            proposal.height(),
+            proposal.round(),
+            proposal.step(),
+            Some(&proposed_block_id),
+        )?;
+
+        let sign_bytes = proposal.sign_bytes(&self.chain_id);
+        let signature = self.ed25519_sign(&pk, &sign_bytes)?;
+
+        Ok(Response::SignProposal(SignResponse {
+            sig: signature.as_bytes().to_vec(),
+        }))
+    }
+
+    /// Sign an opaque (non-Tendermint-consensus) message with the key and
+    /// algorithm named in the request, e.g. a secp256k1 account key or a
+    /// BLS12-381 sync-committee key. Not subject to slashing protection,
+    /// since only Tendermint votes and proposals can be used to equivocate.
+    fn sign_raw(&mut self, request: &SignRequest) -> Result<Response, Error> {
+        let pk = PublicKey::from_bytes(request.key_type, &request.public_key)?;
+        let signature = self.keyring.sign(&pk, &request.msg)?;
+        Keyring::verify(&pk, &request.msg, &signature)?;
+
+        Ok(Response::SignRaw(SignResponse {
+            sig: signature.to_bytes(),
+        }))
+    }
+
+    /// Sign with an Ed25519 key via the generalized keyring, then verify the
+    /// returned signature before handing it back to the validator
+    fn ed25519_sign(
+        &self,
+        pk: &ed25519::PublicKey,
+        sign_bytes: &[u8],
+    ) -> Result<ed25519::Signature, Error> {
+        let signature = self.keyring.sign(&PublicKey::Ed25519(pk.clone()), sign_bytes)?;
+
+        match signature {
+            Signature::Ed25519(ref sig) => {
+                Keyring::verify(&PublicKey::Ed25519(pk.clone()), sign_bytes, &signature)?;
+                Ok(*sig)
+            }
+            _ => Err(Error::InvalidKey(
+                "expected an Ed25519 signature for a consensus message".into(),
+            )),
+        }
+    }
+}
+
+/// Derive the 20-byte validator address Tendermint uses to identify a
+/// validator's consensus stream: the SHA-256 hash of the raw public key,
+/// truncated to 20 bytes.
+fn address_from_pubkey(pk: &ed25519::PublicKey) -> ValidatorAddress {
+    Sha256::digest(pk.as_bytes())[..20].to_vec()
 }
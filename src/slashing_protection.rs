@@ -0,0 +1,426 @@
+//! Slashing-protection high-water-mark store
+//!
+//! Tracks the last `(height, round, step)` the KMS has signed for each
+//! `(chain_id, validator_address)` pair and refuses any request that would
+//! sign below that mark, or at the same mark for a different block -- the
+//! two ways a validator can get slashed for double-signing.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use error::Error;
+use types::{BlockID, PartsSetHeader, ValidatorAddress};
+
+/// Ordering of the messages a validator signs during a round: a proposal
+/// always precedes the votes cast on it, which in turn precede a commit.
+/// This mirrors Tendermint's internal last-sign-state step enum used for
+/// high-water-mark comparison, which is distinct from (and numbered
+/// differently than) the `SignedMsgType` tag `vote_type_to_char` encodes
+/// into the wire `Vote` message.
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
+pub struct Step(pub u8);
+
+impl Step {
+    /// A `Proposal` message
+    pub const PROPOSAL: Step = Step(0x01);
+
+    /// A `PreVote` message
+    pub const PREVOTE: Step = Step(0x02);
+
+    /// A `PreCommit` message
+    pub const PRECOMMIT: Step = Step(0x03);
+}
+
+/// The `(height, round, step)` coordinates of a signed consensus message,
+/// plus the block it committed to (if any)
+#[derive(Clone, Debug)]
+struct SignState {
+    height: i64,
+    round: i64,
+    step: Step,
+    block_id: Option<BlockID>,
+}
+
+impl SignState {
+    fn coordinates(&self) -> (i64, i64, u8) {
+        (self.height, self.round, self.step.0)
+    }
+}
+
+/// Key identifying a particular validator's consensus stream on a chain
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+struct ConsensusKey {
+    chain_id: String,
+    validator_address: ValidatorAddress,
+}
+
+/// Persistent high-water-mark store, consulted before every signature
+pub struct SlashingProtection {
+    path: PathBuf,
+    state: HashMap<ConsensusKey, SignState>,
+}
+
+impl SlashingProtection {
+    /// Load (or initialize) the high-water-mark store at the given path
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+
+        let state = if path.exists() {
+            let mut contents = String::new();
+            File::open(&path)?.read_to_string(&mut contents)?;
+            Self::parse(&contents)?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self { path, state })
+    }
+
+    /// Check a prospective signature against the high-water mark, and if it
+    /// is permitted, persist the new mark *before* returning.
+    ///
+    /// Signing is permitted when `(height, round, step)` is strictly greater
+    /// than the stored mark for this `(chain_id, validator_address)`, or
+    /// when it exactly matches the stored mark *and* `block_id` is
+    /// byte-for-byte identical (an idempotent retransmit of the same vote).
+    pub fn check_and_update(
+        &mut self,
+        chain_id: &str,
+        validator_address: &ValidatorAddress,
+        height: i64,
+        round: i64,
+        step: Step,
+        block_id: Option<&BlockID>,
+    ) -> Result<(), Error> {
+        let key = ConsensusKey {
+            chain_id: chain_id.to_owned(),
+            validator_address: validator_address.clone(),
+        };
+
+        let candidate = SignState {
+            height,
+            round,
+            step,
+            block_id: block_id.cloned(),
+        };
+
+        if let Some(last) = self.state.get(&key) {
+            if candidate.coordinates() < last.coordinates() {
+                return Err(Error::SigningProhibited(format!(
+                    "double sign attempt: ({}, {}, {:?}) is behind high water mark ({}, {}, {:?})",
+                    height, round, step, last.height, last.round, last.step
+                )));
+            }
+
+            if candidate.coordinates() == last.coordinates() && candidate.block_id != last.block_id
+            {
+                return Err(Error::SigningProhibited(format!(
+                    "equivocation attempt: differing block id at ({}, {}, {:?})",
+                    height, round, step
+                )));
+            }
+        }
+
+        self.state.insert(key, candidate);
+        self.persist()
+    }
+
+    /// Atomically persist the current state (write-temp-then-rename, so a
+    /// crash mid-write can never leave a corrupt or half-written mark file)
+    fn persist(&self) -> Result<(), Error> {
+        let tmp_path = self.path.with_extension("tmp");
+
+        {
+            let mut tmp_file = File::create(&tmp_path)?;
+            tmp_file.write_all(Self::serialize(&self.state).as_bytes())?;
+            tmp_file.sync_all()?;
+        }
+
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Serialize the high-water marks as simple tab-separated lines:
+    /// `chain_id\tvalidator_address_hex\theight\tround\tstep\tblock_id`, where
+    /// `block_id` is `hash_hex:parts_total:parts_hash_hex` (empty when none)
+    fn serialize(state: &HashMap<ConsensusKey, SignState>) -> String {
+        let mut out = String::new();
+
+        for (key, mark) in state {
+            let block_id_hex = mark
+                .block_id
+                .as_ref()
+                .map(|b| format!("{}:{}:{}", hex(&b.hash), b.parts_header.total, hex(&b.parts_header.hash)))
+                .unwrap_or_default();
+
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\n",
+                key.chain_id,
+                hex(&key.validator_address),
+                mark.height,
+                mark.round,
+                mark.step.0,
+                block_id_hex
+            ));
+        }
+
+        out
+    }
+
+    /// Parse the tab-separated high-water-mark format written by `serialize`.
+    ///
+    /// Every line must parse in full: this store exists to stop double
+    /// signing, so a truncated or garbled state file must refuse to load
+    /// rather than silently drop or zero out the fields it can't read,
+    /// which would quietly lower the high-water mark it's supposed to
+    /// enforce.
+    fn parse(contents: &str) -> Result<HashMap<ConsensusKey, SignState>, Error> {
+        let mut state = HashMap::new();
+
+        for line in contents.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 6 {
+                return Err(Error::Parse(format!(
+                    "malformed high-water-mark line (expected 6 fields, got {}): {:?}",
+                    fields.len(),
+                    line
+                )));
+            }
+
+            let block_id = if fields[5].is_empty() {
+                None
+            } else {
+                let parts: Vec<&str> = fields[5].splitn(3, ':').collect();
+                if parts.len() != 3 {
+                    return Err(Error::Parse(format!(
+                        "malformed block id field in high-water-mark line: {:?}",
+                        line
+                    )));
+                }
+                Some(BlockID {
+                    hash: unhex(parts[0])?,
+                    parts_header: PartsSetHeader {
+                        total: parts[1].parse().map_err(|_| {
+                            Error::Parse(format!(
+                                "invalid parts total in high-water-mark line: {:?}",
+                                line
+                            ))
+                        })?,
+                        hash: unhex(parts[2])?,
+                    },
+                })
+            };
+
+            let key = ConsensusKey {
+                chain_id: fields[0].to_owned(),
+                validator_address: unhex(fields[1])?,
+            };
+
+            let mark = SignState {
+                height: fields[2].parse().map_err(|_| {
+                    Error::Parse(format!("invalid height in high-water-mark line: {:?}", line))
+                })?,
+                round: fields[3].parse().map_err(|_| {
+                    Error::Parse(format!("invalid round in high-water-mark line: {:?}", line))
+                })?,
+                step: Step(fields[4].parse().map_err(|_| {
+                    Error::Parse(format!("invalid step in high-water-mark line: {:?}", line))
+                })?),
+                block_id,
+            };
+
+            state.insert(key, mark);
+        }
+
+        Ok(state)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn unhex(s: &str) -> Result<Vec<u8>, Error> {
+    if s.len() % 2 != 0 {
+        return Err(Error::Parse(format!(
+            "odd-length hex field in high-water-mark line: {:?}",
+            s
+        )));
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&s[i..i + 2], 16).map_err(|_| {
+                Error::Parse(format!("invalid hex byte in high-water-mark line: {:?}", s))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("kms-slashing-protection-test-{}-{}", std::process::id(), name))
+    }
+
+    fn cleanup(path: &PathBuf) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(path.with_extension("tmp"));
+    }
+
+    #[test]
+    fn test_step_orders_propose_before_prevote_before_precommit() {
+        assert!(Step::PROPOSAL < Step::PREVOTE);
+        assert!(Step::PREVOTE < Step::PRECOMMIT);
+    }
+
+    #[test]
+    fn test_check_and_update_permits_strictly_increasing_height() {
+        let path = temp_path("increasing");
+        cleanup(&path);
+        let mut sp = SlashingProtection::load(&path).unwrap();
+
+        sp.check_and_update("test-chain", &vec![1u8; 20], 1, 0, Step::PREVOTE, None)
+            .unwrap();
+        sp.check_and_update("test-chain", &vec![1u8; 20], 2, 0, Step::PREVOTE, None)
+            .unwrap();
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_check_and_update_rejects_signing_behind_high_water_mark() {
+        let path = temp_path("behind");
+        cleanup(&path);
+        let mut sp = SlashingProtection::load(&path).unwrap();
+
+        sp.check_and_update("test-chain", &vec![1u8; 20], 5, 0, Step::PREVOTE, None)
+            .unwrap();
+        match sp.check_and_update("test-chain", &vec![1u8; 20], 4, 0, Step::PREVOTE, None) {
+            Err(Error::SigningProhibited(_)) => {}
+            other => panic!("expected SigningProhibited, got {:?}", other),
+        }
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_check_and_update_permits_idempotent_retransmit() {
+        let path = temp_path("idempotent");
+        cleanup(&path);
+        let mut sp = SlashingProtection::load(&path).unwrap();
+
+        let block_id = BlockID {
+            hash: b"hash".to_vec(),
+            parts_header: PartsSetHeader {
+                total: 1,
+                hash: b"parts".to_vec(),
+            },
+        };
+        sp.check_and_update("test-chain", &vec![1u8; 20], 10, 0, Step::PREVOTE, Some(&block_id))
+            .unwrap();
+        sp.check_and_update("test-chain", &vec![1u8; 20], 10, 0, Step::PREVOTE, Some(&block_id))
+            .unwrap();
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_check_and_update_rejects_equivocation() {
+        let path = temp_path("equivocation");
+        cleanup(&path);
+        let mut sp = SlashingProtection::load(&path).unwrap();
+
+        let block_a = BlockID {
+            hash: b"a".to_vec(),
+            parts_header: PartsSetHeader { total: 1, hash: vec![] },
+        };
+        let block_b = BlockID {
+            hash: b"b".to_vec(),
+            parts_header: PartsSetHeader { total: 1, hash: vec![] },
+        };
+        sp.check_and_update("test-chain", &vec![1u8; 20], 10, 0, Step::PREVOTE, Some(&block_a))
+            .unwrap();
+        match sp.check_and_update("test-chain", &vec![1u8; 20], 10, 0, Step::PREVOTE, Some(&block_b)) {
+            Err(Error::SigningProhibited(_)) => {}
+            other => panic!("expected SigningProhibited, got {:?}", other),
+        }
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_load_rejects_truncated_line() {
+        let path = temp_path("truncated");
+        cleanup(&path);
+
+        fs::write(&path, "test-chain\t0101010101010101010101010101010101010101\t10\t0\n").unwrap();
+
+        match SlashingProtection::load(&path) {
+            Err(Error::Parse(_)) => {}
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_hex_validator_address() {
+        let path = temp_path("bad-hex");
+        cleanup(&path);
+
+        fs::write(&path, "test-chain\tnot-hex\t10\t0\t2\t\n").unwrap();
+
+        match SlashingProtection::load(&path) {
+            Err(Error::Parse(_)) => {}
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_load_rejects_non_numeric_height() {
+        let path = temp_path("bad-height");
+        cleanup(&path);
+
+        fs::write(
+            &path,
+            "test-chain\t0101010101010101010101010101010101010101\tnot-a-number\t0\t2\t\n",
+        )
+        .unwrap();
+
+        match SlashingProtection::load(&path) {
+            Err(Error::Parse(_)) => {}
+            other => panic!("expected Parse error, got {:?}", other),
+        }
+
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_check_and_update_persists_across_reload() {
+        let path = temp_path("persist");
+        cleanup(&path);
+
+        {
+            let mut sp = SlashingProtection::load(&path).unwrap();
+            sp.check_and_update("test-chain", &vec![2u8; 20], 42, 1, Step::PRECOMMIT, None)
+                .unwrap();
+        }
+        {
+            let mut sp = SlashingProtection::load(&path).unwrap();
+            match sp.check_and_update("test-chain", &vec![2u8; 20], 41, 1, Step::PRECOMMIT, None) {
+                Err(Error::SigningProhibited(_)) => {}
+                other => panic!("expected SigningProhibited, got {:?}", other),
+            }
+        }
+
+        cleanup(&path);
+    }
+}
@@ -0,0 +1,96 @@
+//! Amino-encoded wire types for the Tendermint consensus messages a validator signs
+
+pub mod proposal;
+pub mod vote;
+
+pub use self::proposal::Proposal;
+pub use self::vote::Vote;
+
+use amino::*;
+use bytes::Buf;
+use std::io::Cursor;
+
+/// Raw 20-byte validator address
+pub type ValidatorAddress = Vec<u8>;
+
+/// Types which can be reduced to the bytes a validator actually signs
+/// (`SignBytes` in the Tendermint Go implementation)
+pub trait TendermintSign {
+    /// Produce the canonical JSON representation of this message for the given chain
+    fn cannonicalize(self, chain_id: &str) -> String;
+
+    /// Produce the canonical length-prefixed Amino encoding of this message
+    /// (`CanonicalVote`/`CanonicalProposal`): the fields a validator signs,
+    /// in canonical order, with `chain_id` appended as the final field and
+    /// the (not-yet-computed) signature omitted entirely.
+    fn sign_bytes(&self, chain_id: &str) -> Vec<u8>;
+}
+
+/// Part-set header embedded within a `BlockID`
+#[derive(PartialEq, Clone, Debug)]
+pub struct PartsSetHeader {
+    /// Total number of parts in the set
+    pub total: i64,
+
+    /// Hash of the part-set header
+    pub hash: Vec<u8>,
+}
+
+/// Reference to the block a `Vote` or `Proposal` commits to
+#[derive(PartialEq, Clone, Debug)]
+pub struct BlockID {
+    /// Block hash
+    pub hash: Vec<u8>,
+
+    /// Part-set header for the block
+    pub parts_header: PartsSetHeader,
+}
+
+impl BlockID {
+    /// Decode a `BlockID` occupying the given amino field number
+    pub fn decode(field_number: u32, buf: &mut Cursor<&[u8]>) -> Result<Self, DecodeError> {
+        check_field_number_typ3(field_number, Typ3Byte::Typ3_Struct, buf)?;
+
+        let mut hash = vec![];
+        let mut next_typ3 = buf.get_u8();
+
+        let hash_field_prefix = 1 << 3 | typ3_to_byte(Typ3Byte::Typ3_ByteLength);
+        if next_typ3 == hash_field_prefix {
+            hash = amino_bytes::decode(buf)?;
+            next_typ3 = buf.get_u8();
+        }
+
+        let parts_header_field_prefix = 2 << 3 | typ3_to_byte(Typ3Byte::Typ3_Struct);
+        if next_typ3 != parts_header_field_prefix {
+            return Err(DecodeError::new("missing parts header in block id"));
+        }
+
+        check_field_number_typ3(1, Typ3Byte::Typ3_Varint, buf)?;
+        let total = decode_varint(buf)?;
+
+        let mut parts_hash = vec![];
+        let mut after_total_typ3 = buf.get_u8();
+
+        let parts_hash_field_prefix = 2 << 3 | typ3_to_byte(Typ3Byte::Typ3_ByteLength);
+        if after_total_typ3 == parts_hash_field_prefix {
+            parts_hash = amino_bytes::decode(buf)?;
+            after_total_typ3 = buf.get_u8();
+        }
+
+        let struct_term = typ3_to_byte(Typ3Byte::Typ3_StructTerm);
+        if after_total_typ3 != struct_term {
+            return Err(DecodeError::new("invalid type for parts header struct term"));
+        }
+        if buf.get_u8() != struct_term {
+            return Err(DecodeError::new("invalid type for block id struct term"));
+        }
+
+        Ok(BlockID {
+            hash,
+            parts_header: PartsSetHeader {
+                total,
+                hash: parts_hash,
+            },
+        })
+    }
+}
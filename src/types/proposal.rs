@@ -0,0 +1,355 @@
+use super::{BlockID, PartsSetHeader, TendermintSign};
+use amino::*;
+use bytes::{Buf, BufMut};
+use chrono::{DateTime, Utc};
+use hex::encode_upper;
+use signatory::ed25519::{Signature, SIGNATURE_SIZE};
+use slashing_protection::Step;
+use std::io::Cursor;
+
+#[derive(PartialEq, Debug)]
+pub struct Proposal {
+    height: i64,
+    round: i64,
+    timestamp: DateTime<Utc>,
+    block_parts_header: PartsSetHeader,
+    pol_round: i64,
+    pol_block_id: Option<BlockID>,
+    signature: Option<Signature>,
+}
+
+impl Proposal {
+    /// Block height this proposal is for
+    pub fn height(&self) -> i64 {
+        self.height
+    }
+
+    /// Consensus round this proposal is for
+    pub fn round(&self) -> i64 {
+        self.round
+    }
+
+    /// Block being proposed, identified by its part-set header
+    pub fn block_parts_header(&self) -> &PartsSetHeader {
+        &self.block_parts_header
+    }
+
+    /// Slashing-protection step for this proposal, for high-water-mark comparison
+    pub fn step(&self) -> Step {
+        Step::PROPOSAL
+    }
+}
+
+impl TendermintSign for Proposal {
+    fn cannonicalize(self, chain_id: &str) -> String {
+        let value = json!({
+            "@chain_id":chain_id,
+            "@type":"proposal",
+            "block_parts_header":{
+                "hash":encode_upper(self.block_parts_header.hash),
+                "total":self.block_parts_header.total
+            },
+            "height":self.height,
+            "pol_block_id":self.pol_block_id.map(|b| json!({
+                "hash":encode_upper(b.hash),
+                "parts":{
+                    "hash":encode_upper(b.parts_header.hash),
+                    "total":b.parts_header.total
+                }
+            })),
+            "pol_round":self.pol_round,
+            "round":self.round,
+            "timestamp":self.timestamp.to_rfc3339()
+            });
+        value.to_string()
+    }
+
+    fn sign_bytes(&self, chain_id: &str) -> Vec<u8> {
+        let mut buf = vec![];
+
+        encode_field_number_typ3(1, Typ3Byte::Typ3_8Byte, &mut buf);
+        encode_int64(self.height, &mut buf);
+
+        encode_field_number_typ3(2, Typ3Byte::Typ3_Varint, &mut buf);
+        encode_varint(self.round, &mut buf);
+
+        encode_field_number_typ3(3, Typ3Byte::Typ3_Struct, &mut buf);
+        amino_time::encode(self.timestamp, &mut buf);
+        // amino_time::encode takes care of Typ3_StructTerm
+
+        encode_field_number_typ3(4, Typ3Byte::Typ3_Struct, &mut buf);
+        {
+            encode_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf);
+            encode_varint(self.block_parts_header.total, &mut buf);
+
+            if !&self.block_parts_header.hash.is_empty() {
+                encode_field_number_typ3(2, Typ3Byte::Typ3_ByteLength, &mut buf);
+                amino_bytes::encode(&self.block_parts_header.hash, &mut buf)
+            }
+        }
+        buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+
+        encode_field_number_typ3(5, Typ3Byte::Typ3_Varint, &mut buf);
+        encode_varint(self.pol_round, &mut buf);
+
+        if let Some(ref pol_block_id) = self.pol_block_id {
+            encode_field_number_typ3(6, Typ3Byte::Typ3_Struct, &mut buf);
+            {
+                if !&pol_block_id.hash.is_empty() {
+                    encode_field_number_typ3(1, Typ3Byte::Typ3_ByteLength, &mut buf);
+                    amino_bytes::encode(&pol_block_id.hash, &mut buf);
+                }
+
+                encode_field_number_typ3(2, Typ3Byte::Typ3_Struct, &mut buf);
+                {
+                    encode_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf);
+                    encode_varint(pol_block_id.parts_header.total, &mut buf);
+
+                    if !&pol_block_id.parts_header.hash.is_empty() {
+                        encode_field_number_typ3(2, Typ3Byte::Typ3_ByteLength, &mut buf);
+                        amino_bytes::encode(&pol_block_id.parts_header.hash, &mut buf)
+                    }
+                }
+                buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+            }
+            buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+        }
+
+        // chain_id is appended last; the signature is never part of sign bytes
+        if !chain_id.is_empty() {
+            encode_field_number_typ3(7, Typ3Byte::Typ3_ByteLength, &mut buf);
+            amino_bytes::encode(chain_id.as_bytes(), &mut buf);
+        }
+
+        let mut length_buf = vec![];
+        encode_uvarint(buf.len() as u64, &mut length_buf);
+        length_buf.append(&mut buf);
+
+        length_buf
+    }
+}
+
+impl Amino for Proposal {
+    fn serialize(self) -> Vec<u8> {
+        let mut buf = vec![];
+        let (_dis, mut pre) = compute_disfix("tendermint/socketpv/SignProposalMsg");
+
+        pre[3] |= typ3_to_byte(Typ3Byte::Typ3_Struct);
+        buf.put_slice(pre.as_slice());
+        {
+            encode_field_number_typ3(1, Typ3Byte::Typ3_Struct, &mut buf);
+            {
+                // Encode the height
+                encode_field_number_typ3(1, Typ3Byte::Typ3_8Byte, &mut buf);
+                encode_int64(self.height, &mut buf);
+
+                // Encode the round
+                encode_field_number_typ3(2, Typ3Byte::Typ3_Varint, &mut buf);
+                encode_varint(self.round, &mut buf);
+
+                // Encode the timestamp
+                encode_field_number_typ3(3, Typ3Byte::Typ3_Struct, &mut buf);
+                amino_time::encode(self.timestamp, &mut buf);
+                // amino_time::encode takes care of Typ3_StructTerm
+
+                // Encode the proposed block's part-set header
+                encode_field_number_typ3(4, Typ3Byte::Typ3_Struct, &mut buf);
+                {
+                    encode_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf);
+                    encode_varint(self.block_parts_header.total, &mut buf);
+
+                    if !&self.block_parts_header.hash.is_empty() {
+                        encode_field_number_typ3(2, Typ3Byte::Typ3_ByteLength, &mut buf);
+                        amino_bytes::encode(&self.block_parts_header.hash, &mut buf)
+                    }
+                }
+                buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+
+                // Encode the proof-of-lock round
+                encode_field_number_typ3(5, Typ3Byte::Typ3_Varint, &mut buf);
+                encode_varint(self.pol_round, &mut buf);
+
+                // Encode the proof-of-lock block id, if there is one
+                if let Some(pol_block_id) = self.pol_block_id {
+                    encode_field_number_typ3(6, Typ3Byte::Typ3_Struct, &mut buf);
+                    {
+                        if !&pol_block_id.hash.is_empty() {
+                            encode_field_number_typ3(1, Typ3Byte::Typ3_ByteLength, &mut buf);
+                            amino_bytes::encode(&pol_block_id.hash, &mut buf);
+                        }
+
+                        encode_field_number_typ3(2, Typ3Byte::Typ3_Struct, &mut buf);
+                        {
+                            encode_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf);
+                            encode_varint(pol_block_id.parts_header.total, &mut buf);
+
+                            if !&pol_block_id.parts_header.hash.is_empty() {
+                                encode_field_number_typ3(2, Typ3Byte::Typ3_ByteLength, &mut buf);
+                                amino_bytes::encode(&pol_block_id.parts_header.hash, &mut buf)
+                            }
+                        }
+                        buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+                    }
+                    buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+                }
+
+                // Encode Signature:
+                if let Some(sig) = self.signature {
+                    encode_field_number_typ3(7, Typ3Byte::Typ3_Interface, &mut buf);
+                    amino_bytes::encode(&sig.0, &mut buf)
+                }
+            }
+            // signal end of main struct
+            buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+        }
+        // we are done here ...
+        buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+
+        let mut length_buf = vec![];
+        encode_uvarint(buf.len() as u64, &mut length_buf);
+        length_buf.append(&mut buf);
+
+        length_buf
+    }
+
+    fn deserialize(data: &[u8]) -> Result<Proposal, DecodeError> {
+        let mut buf = Cursor::new(data);
+        consume_length(&mut buf)?;
+        consume_prefix(&mut buf, "tendermint/socketpv/SignProposalMsg")?;
+        check_field_number_typ3(1, Typ3Byte::Typ3_Struct, &mut buf)?;
+
+        check_field_number_typ3(1, Typ3Byte::Typ3_8Byte, &mut buf)?;
+        let height = decode_int64(&mut buf)?;
+
+        check_field_number_typ3(2, Typ3Byte::Typ3_Varint, &mut buf)?;
+        let round = decode_varint(&mut buf)?;
+
+        check_field_number_typ3(3, Typ3Byte::Typ3_Struct, &mut buf)?;
+        let timestamp = amino_time::decode(&mut buf)?;
+
+        check_field_number_typ3(4, Typ3Byte::Typ3_Struct, &mut buf)?;
+        check_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf)?;
+        let parts_total = decode_varint(&mut buf)?;
+
+        let mut next_typ3 = buf.get_u8();
+        let parts_hash_field_prefix = 2 << 3 | typ3_to_byte(Typ3Byte::Typ3_ByteLength);
+        let mut parts_hash = vec![];
+        if next_typ3 == parts_hash_field_prefix {
+            parts_hash = amino_bytes::decode(&mut buf)?;
+            next_typ3 = buf.get_u8();
+        }
+        let struct_term = typ3_to_byte(Typ3Byte::Typ3_StructTerm);
+        if next_typ3 != struct_term {
+            return Err(DecodeError::new("invalid type for block parts header struct term"));
+        }
+        let block_parts_header = PartsSetHeader {
+            total: parts_total,
+            hash: parts_hash,
+        };
+
+        check_field_number_typ3(5, Typ3Byte::Typ3_Varint, &mut buf)?;
+        let pol_round = decode_varint(&mut buf)?;
+
+        let mut optional_typ3 = buf.get_u8();
+        let pol_block_id_field_prefix = 6 << 3 | typ3_to_byte(Typ3Byte::Typ3_Struct);
+        let pol_block_id = if optional_typ3 == pol_block_id_field_prefix {
+            // rewind so BlockID::decode can re-read (and check) the field prefix itself
+            buf.set_position(buf.position() - 1);
+            let decoded = BlockID::decode(6, &mut buf)?;
+            optional_typ3 = buf.get_u8();
+            Some(decoded)
+        } else {
+            None
+        };
+
+        let mut signature: Option<Signature> = None;
+        let sig_field_prefix = 7 << 3 | typ3_to_byte(Typ3Byte::Typ3_Interface);
+        if optional_typ3 == sig_field_prefix {
+            let mut signature_array: [u8; SIGNATURE_SIZE] = [0; SIGNATURE_SIZE];
+            signature_array.copy_from_slice(amino_bytes::decode(&mut buf)?.as_slice());
+            signature = Some(Signature(signature_array));
+
+            optional_typ3 = buf.get_u8();
+        }
+        let struct_end_postfix = typ3_to_byte(Typ3Byte::Typ3_StructTerm);
+        if optional_typ3 != struct_end_postfix {
+            return Err(DecodeError::new("invalid type for first struct term"));
+        }
+
+        Ok(Proposal {
+            height,
+            round,
+            timestamp,
+            block_parts_header,
+            pol_round,
+            pol_block_id,
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::BlockID;
+
+    #[test]
+    fn test_sign_bytes() {
+        {
+            let proposal = Proposal {
+                height: 100,
+                round: 1,
+                timestamp: "2017-12-25T03:00:01.234Z".parse::<DateTime<Utc>>().unwrap(),
+                block_parts_header: PartsSetHeader {
+                    total: 1000000,
+                    hash: "parts_hash".as_bytes().to_vec(),
+                },
+                pol_round: 0,
+                pol_block_id: None,
+                signature: None,
+            };
+
+            let have = proposal.sign_bytes("test-chain");
+            let want = vec![
+                0x3b, 0x9, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x64, 0x10, 0x2, 0x1b, 0x9, 0x0,
+                0x0, 0x0, 0x0, 0x5a, 0x40, 0x69, 0xb1, 0x15, 0xd, 0xf2, 0x8e, 0x80, 0x4, 0x23,
+                0x8, 0x80, 0x89, 0x7a, 0x12, 0xa, 0x70, 0x61, 0x72, 0x74, 0x73, 0x5f, 0x68, 0x61,
+                0x73, 0x68, 0x4, 0x28, 0x0, 0x3a, 0xa, 0x74, 0x65, 0x73, 0x74, 0x2d, 0x63, 0x68,
+                0x61, 0x69, 0x6e,
+            ];
+            assert_eq!(have, want)
+        }
+        {
+            // with a proof-of-lock block id set (round > 0)
+            let proposal = Proposal {
+                height: 100,
+                round: 1,
+                timestamp: "2017-12-25T03:00:01.234Z".parse::<DateTime<Utc>>().unwrap(),
+                block_parts_header: PartsSetHeader {
+                    total: 1000000,
+                    hash: "parts_hash".as_bytes().to_vec(),
+                },
+                pol_round: 2,
+                pol_block_id: Some(BlockID {
+                    hash: "polhash".as_bytes().to_vec(),
+                    parts_header: PartsSetHeader {
+                        total: 10,
+                        hash: "polparts".as_bytes().to_vec(),
+                    },
+                }),
+                signature: None,
+            };
+
+            let have = proposal.sign_bytes("test-chain");
+            let want = vec![
+                0x54, 0x9, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x64, 0x10, 0x2, 0x1b, 0x9, 0x0,
+                0x0, 0x0, 0x0, 0x5a, 0x40, 0x69, 0xb1, 0x15, 0xd, 0xf2, 0x8e, 0x80, 0x4, 0x23,
+                0x8, 0x80, 0x89, 0x7a, 0x12, 0xa, 0x70, 0x61, 0x72, 0x74, 0x73, 0x5f, 0x68, 0x61,
+                0x73, 0x68, 0x4, 0x28, 0x4, 0x33, 0xa, 0x7, 0x70, 0x6f, 0x6c, 0x68, 0x61, 0x73,
+                0x68, 0x13, 0x8, 0x14, 0x12, 0x8, 0x70, 0x6f, 0x6c, 0x70, 0x61, 0x72, 0x74, 0x73,
+                0x4, 0x4, 0x3a, 0xa, 0x74, 0x65, 0x73, 0x74, 0x2d, 0x63, 0x68, 0x61, 0x69, 0x6e,
+            ];
+            assert_eq!(have, want)
+        }
+    }
+}
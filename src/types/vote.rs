@@ -4,9 +4,10 @@ use bytes::{Buf, BufMut};
 use chrono::{DateTime, Utc};
 use hex::encode_upper;
 use signatory::ed25519::{Signature, SIGNATURE_SIZE};
+use slashing_protection::Step;
 use std::io::Cursor;
 
-#[derive(PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 enum VoteType {
     PreVote,
     PreCommit,
@@ -39,6 +40,36 @@ pub struct Vote {
     signature: Option<Signature>,
 }
 
+impl Vote {
+    /// Address of the validator that cast this vote
+    pub fn validator_address(&self) -> &ValidatorAddress {
+        &self.validator_address
+    }
+
+    /// Block height this vote was cast at
+    pub fn height(&self) -> i64 {
+        self.height
+    }
+
+    /// Consensus round this vote was cast at
+    pub fn round(&self) -> i64 {
+        self.round
+    }
+
+    /// Block this vote commits to
+    pub fn block_id(&self) -> &BlockID {
+        &self.block_id
+    }
+
+    /// Slashing-protection step for this vote, for high-water-mark comparison
+    pub fn step(&self) -> Step {
+        match self.vote_type {
+            VoteType::PreVote => Step::PREVOTE,
+            VoteType::PreCommit => Step::PRECOMMIT,
+        }
+    }
+}
+
 impl TendermintSign for Vote {
     fn cannonicalize(self, chain_id: &str) -> String {
         let value = json!({
@@ -58,6 +89,57 @@ impl TendermintSign for Vote {
             });
         value.to_string()
     }
+
+    fn sign_bytes(&self, chain_id: &str) -> Vec<u8> {
+        let mut buf = vec![];
+
+        encode_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf);
+        encode_uint8(vote_type_to_char(self.vote_type) as u8, &mut buf);
+
+        encode_field_number_typ3(2, Typ3Byte::Typ3_8Byte, &mut buf);
+        encode_int64(self.height, &mut buf);
+
+        encode_field_number_typ3(3, Typ3Byte::Typ3_Varint, &mut buf);
+        encode_varint(self.round, &mut buf);
+
+        // Encode BlockID (struct)
+        encode_field_number_typ3(4, Typ3Byte::Typ3_Struct, &mut buf);
+        {
+            if !&self.block_id.hash.is_empty() {
+                encode_field_number_typ3(1, Typ3Byte::Typ3_ByteLength, &mut buf);
+                amino_bytes::encode(&self.block_id.hash, &mut buf);
+            }
+
+            encode_field_number_typ3(2, Typ3Byte::Typ3_Struct, &mut buf);
+            {
+                encode_field_number_typ3(1, Typ3Byte::Typ3_Varint, &mut buf);
+                encode_varint(self.block_id.parts_header.total, &mut buf);
+
+                if !&self.block_id.parts_header.hash.is_empty() {
+                    encode_field_number_typ3(2, Typ3Byte::Typ3_ByteLength, &mut buf);
+                    amino_bytes::encode(&self.block_id.parts_header.hash, &mut buf)
+                }
+            }
+            buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+        }
+        buf.put(typ3_to_byte(Typ3Byte::Typ3_StructTerm));
+
+        encode_field_number_typ3(5, Typ3Byte::Typ3_Struct, &mut buf);
+        amino_time::encode(self.timestamp, &mut buf);
+        // amino_time::encode takes care of Typ3_StructTerm
+
+        // chain_id is appended last; the signature is never part of sign bytes
+        if !chain_id.is_empty() {
+            encode_field_number_typ3(6, Typ3Byte::Typ3_ByteLength, &mut buf);
+            amino_bytes::encode(chain_id.as_bytes(), &mut buf);
+        }
+
+        let mut length_buf = vec![];
+        encode_uvarint(buf.len() as u64, &mut length_buf);
+        length_buf.append(&mut buf);
+
+        length_buf
+    }
 }
 
 impl Amino for Vote {
@@ -305,4 +387,68 @@ mod tests {
         }
     }
     //ToDo Serialization with Signature
+
+    #[test]
+    fn test_sign_bytes() {
+        let addr: [u8; 20] = [
+            0xa3, 0xb2, 0xcc, 0xdd, 0x71, 0x86, 0xf1, 0x68, 0x5f, 0x21, 0xf2, 0x48, 0x2a, 0xf4,
+            0xfb, 0x34, 0x46, 0xa8, 0x4b, 0x35,
+        ];
+        {
+            let vote = Vote {
+                validator_address: addr.to_vec(),
+                validator_index: 56789,
+                height: 12345,
+                round: 2,
+                timestamp: "2017-12-25T03:00:01.234Z".parse::<DateTime<Utc>>().unwrap(),
+                vote_type: VoteType::PreVote,
+                block_id: BlockID {
+                    hash: "hash".as_bytes().to_vec(),
+                    parts_header: PartsSetHeader {
+                        total: 1000000,
+                        hash: "parts_hash".as_bytes().to_vec(),
+                    },
+                },
+                signature: None,
+            };
+
+            let have = vote.sign_bytes("test-chain");
+            let want = vec![
+                0x43, 0x8, 0x1, 0x11, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x30, 0x39, 0x18, 0x4, 0x23,
+                0xa, 0x4, 0x68, 0x61, 0x73, 0x68, 0x13, 0x8, 0x80, 0x89, 0x7a, 0x12, 0xa, 0x70,
+                0x61, 0x72, 0x74, 0x73, 0x5f, 0x68, 0x61, 0x73, 0x68, 0x4, 0x4, 0x2b, 0x9, 0x0,
+                0x0, 0x0, 0x0, 0x5a, 0x40, 0x69, 0xb1, 0x15, 0xd, 0xf2, 0x8e, 0x80, 0x4, 0x32,
+                0xa, 0x74, 0x65, 0x73, 0x74, 0x2d, 0x63, 0x68, 0x61, 0x69, 0x6e,
+            ];
+            assert_eq!(have, want)
+        }
+        {
+            // empty block_id/parts hashes and an empty chain_id (the
+            // `chain_id` field is omitted entirely from the sign bytes)
+            let vote = Vote {
+                validator_address: addr.to_vec(),
+                validator_index: 0,
+                height: 500,
+                round: 0,
+                timestamp: "2017-12-25T03:00:01.234Z".parse::<DateTime<Utc>>().unwrap(),
+                vote_type: VoteType::PreCommit,
+                block_id: BlockID {
+                    hash: vec![],
+                    parts_header: PartsSetHeader {
+                        total: 0,
+                        hash: vec![],
+                    },
+                },
+                signature: None,
+            };
+
+            let have = vote.sign_bytes("");
+            let want = vec![
+                0x23, 0x8, 0x2, 0x11, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x1, 0xf4, 0x18, 0x0, 0x23,
+                0x13, 0x8, 0x0, 0x4, 0x4, 0x2b, 0x9, 0x0, 0x0, 0x0, 0x0, 0x5a, 0x40, 0x69, 0xb1,
+                0x15, 0xd, 0xf2, 0x8e, 0x80, 0x4,
+            ];
+            assert_eq!(have, want)
+        }
+    }
 }